@@ -0,0 +1,302 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::{Ref, RefCell};
+use std::str;
+
+use super::super::Input;
+use super::line_index;
+
+/// A `struct` useful for matching binary or non-UTF-8 data, such as protocols and file formats.
+/// `between` compares raw byte values rather than decoded `char`s, so a grammar can express a
+/// byte range such as `'\u{0}'..'\u{ff}'` even over data that is not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::BytesInput;
+/// let mut input = BytesInput::new(vec![0xff, 0x00, 0xff]);
+///
+/// assert!(input.between('\u{f0}', '\u{ff}'));
+/// ```
+pub struct BytesInput {
+    bytes: Vec<u8>,
+    pos: usize,
+    line_starts: RefCell<Option<Vec<usize>>>
+}
+
+impl BytesInput {
+    /// Creates a new `BytesInput` from a `Vec<u8>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::BytesInput;
+    /// let input = BytesInput::new(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(input.len(), 3);
+    /// ```
+    pub fn new(bytes: Vec<u8>) -> BytesInput {
+        BytesInput {
+            bytes,
+            pos: 0,
+            line_starts: RefCell::new(None)
+        }
+    }
+
+    /// Returns the byte offset starting each line, building and caching the index on first use.
+    fn line_starts(&self) -> Ref<'_, Vec<usize>> {
+        if self.line_starts.borrow().is_none() {
+            *self.line_starts.borrow_mut() = Some(line_index::starts(&self.bytes));
+        }
+
+        Ref::map(self.line_starts.borrow(), |starts| starts.as_ref().unwrap())
+    }
+}
+
+impl Input for BytesInput {
+    #[inline]
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        str::from_utf8(&self.bytes[start..end]).expect("bytes are not valid UTF-8")
+    }
+
+    #[inline]
+    fn slice_bytes(&self, start: usize, end: usize) -> &[u8] {
+        &self.bytes[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        if pos > self.bytes.len() {
+            panic!("position out of bounds");
+        }
+
+        let starts = self.line_starts();
+        let line = line_index::line_of(&starts, pos);
+        let content_start = line_index::content_start(&self.bytes, starts[line]);
+
+        let col = if pos <= content_start {
+            1
+        } else {
+            pos - content_start + 1
+        };
+
+        (line + 1, col)
+    }
+
+    #[inline]
+    fn matches(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.bytes.len() {
+            let result = &self.bytes[self.pos..to] == string.as_bytes();
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    // Same comparison as the trait default, reading `self.bytes` directly instead of going
+    // through `slice_bytes`.
+    #[inline]
+    fn matches_insensitive(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.bytes.len() {
+            let result = self.bytes[self.pos..to].eq_ignore_ascii_case(string.as_bytes());
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn skip_until(&mut self, delim: &str) -> bool {
+        let delim = delim.as_bytes();
+
+        if delim.is_empty() {
+            return true;
+        }
+
+        match self.bytes[self.pos..].windows(delim.len()).position(|window| window == delim) {
+            Some(offset) => {
+                self.pos += offset;
+                true
+            },
+            None => false
+        }
+    }
+
+    #[inline]
+    fn between(&mut self, left: char, right: char) -> bool {
+        let left = left as u32;
+        let right = right as u32;
+
+        if left > 0xff || right > 0xff {
+            panic!("byte ranges should lie between 0x00 and 0xff");
+        }
+
+        let (left, right) = (left as u8, right as u8);
+        let to = self.pos + 1;
+
+        if to <= self.bytes.len() {
+            let byte = self.bytes[self.pos];
+            let result = left <= byte && byte <= right;
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::BytesInput;
+
+    #[test]
+    fn empty() {
+        let mut input = BytesInput::new(vec![]);
+
+        assert!(input.matches(""));
+        assert!(!input.matches("a"));
+    }
+
+    #[test]
+    fn parts() {
+        let mut input = BytesInput::new(b"asdasdf".to_vec());
+
+        assert!(input.matches("asd"));
+        assert!(input.matches("asdf"));
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(BytesInput::new(b"asdasdf".to_vec()).len(), 7);
+    }
+
+    #[test]
+    fn pos() {
+        let mut input = BytesInput::new(b"asdasdf".to_vec());
+
+        assert_eq!(input.pos(), 0);
+        assert!(input.matches("asd"));
+        assert_eq!(input.pos(), 3);
+        assert!(input.matches("asdf"));
+        assert_eq!(input.pos(), 7);
+
+        input.set_pos(3);
+
+        assert_eq!(input.pos(), 3);
+        assert!(input.matches("asdf"));
+        assert_eq!(input.pos(), 7);
+    }
+
+    #[test]
+    fn slice_bytes() {
+        let input = BytesInput::new(vec![0x00, 0xff, 0x42]);
+
+        assert_eq!(input.slice_bytes(1, 3), &[0xff, 0x42]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not valid UTF-8")]
+    fn slice_panics_on_invalid_utf8() {
+        let input = BytesInput::new(vec![b'a', 0xff, b'b']);
+
+        input.slice(0, 3);
+    }
+
+    #[test]
+    fn line_col() {
+        let input = BytesInput::new(b"a\rb\nc\r\nd".to_vec());
+
+        assert_eq!(input.line_col(0), (1, 1));
+        assert_eq!(input.line_col(6), (4, 1));
+        assert_eq!(input.line_col(7), (4, 1));
+        assert_eq!(input.line_col(8), (4, 2));
+    }
+
+    #[test]
+    fn between() {
+        let mut input = BytesInput::new(vec![0xff, 0x00, 0xff]);
+
+        assert!(input.between('\u{0}', '\u{ff}'));
+        assert!(input.between('\u{0}', '\u{0}'));
+        assert!(!input.between('\u{0}', '\u{0}'));
+
+        assert_eq!(input.pos(), 2);
+    }
+
+    #[test]
+    fn matches_insensitive() {
+        let mut input = BytesInput::new(b"SeLeCt".to_vec());
+
+        assert!(input.matches_insensitive("select"));
+        assert_eq!(input.pos(), 6);
+    }
+
+    #[test]
+    fn skip_until() {
+        let mut input = BytesInput::new(b"asd -- qwe".to_vec());
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 4);
+        assert!(!input.skip_until("nope"));
+        assert_eq!(input.pos(), 4);
+    }
+
+    #[test]
+    fn matches_insensitive_ignores_invalid_utf8_elsewhere() {
+        let mut input = BytesInput::new(vec![0xff, b'A', b'b', b'C', 0xfe]);
+
+        input.set_pos(1);
+
+        assert!(input.matches_insensitive("abc"));
+        assert_eq!(input.pos(), 4);
+    }
+
+    #[test]
+    fn skip_until_ignores_invalid_utf8_elsewhere() {
+        let mut input = BytesInput::new(vec![0xff, b'-', b'-', 0xfe]);
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 1);
+    }
+}
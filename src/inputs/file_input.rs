@@ -0,0 +1,313 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::{Ref, RefCell};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::{self, Utf8Error};
+
+use memmap::Mmap;
+
+use super::super::Input;
+use super::line_index;
+
+/// A `struct` useful for matching inputs larger than memory. Memory-maps a file instead of
+/// copying it into an owned `String`, giving O(1) random `slice`/`matches`/`set_pos` while
+/// letting the OS page the file in on demand.
+pub struct FileInput {
+    mmap: Mmap,
+    pos: usize,
+    line_starts: RefCell<Option<Vec<usize>>>
+}
+
+impl FileInput {
+    /// Memory-maps `file` and wraps it as an `Input`. The file's bytes are not checked for
+    /// UTF-8 validity until `validate` is called or a `slice` is read, so opening a
+    /// gigabyte-scale file stays cheap.
+    pub fn new(file: File) -> io::Result<FileInput> {
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Ok(FileInput {
+            mmap,
+            pos: 0,
+            line_starts: RefCell::new(None)
+        })
+    }
+
+    /// Opens and memory-maps the file at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use pest::FileInput;
+    /// let input = FileInput::open("input.txt").unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<FileInput> {
+        FileInput::new(File::open(path)?)
+    }
+
+    /// Eagerly checks that the mapped file is valid UTF-8. `slice` panics on invalid UTF-8, so
+    /// calling this up front turns that panic into a catchable error.
+    pub fn validate(&self) -> Result<(), Utf8Error> {
+        str::from_utf8(&self.mmap).map(|_| ())
+    }
+
+    /// Returns the byte offset starting each line, building and caching the index on first use,
+    /// just like `StringInput` and `StrInput` so `line_col` stays cheap on huge files.
+    fn line_starts(&self) -> Ref<'_, Vec<usize>> {
+        if self.line_starts.borrow().is_none() {
+            *self.line_starts.borrow_mut() = Some(line_index::starts(&self.mmap));
+        }
+
+        Ref::map(self.line_starts.borrow(), |starts| starts.as_ref().unwrap())
+    }
+}
+
+impl Input for FileInput {
+    #[inline]
+    fn len(&self) -> usize {
+        self.mmap.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        str::from_utf8(&self.mmap[start..end]).expect("file is not valid UTF-8; call validate()")
+    }
+
+    #[inline]
+    fn slice_bytes(&self, start: usize, end: usize) -> &[u8] {
+        &self.mmap[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        if pos > self.mmap.len() {
+            panic!("position out of bounds");
+        }
+
+        let starts = self.line_starts();
+        let line = line_index::line_of(&starts, pos);
+        let content_start = line_index::content_start(&self.mmap, starts[line]);
+
+        let col = if pos <= content_start {
+            1
+        } else {
+            self.slice(content_start, pos).chars().count() + 1
+        };
+
+        (line + 1, col)
+    }
+
+    #[inline]
+    fn matches(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.mmap.len() {
+            let result = &self.mmap[self.pos..to] == string.as_bytes();
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    // Same comparison as the trait default, reading the mmap directly instead of going through
+    // `slice_bytes`; only the bytes under comparison are read.
+    #[inline]
+    fn matches_insensitive(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.mmap.len() {
+            let result = self.mmap[self.pos..to].eq_ignore_ascii_case(string.as_bytes());
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    // Overrides the trait default, which slices the whole remaining mmap as a `&str` and so
+    // would UTF-8 validate it on every call and panic on invalid bytes; this searches raw bytes.
+    #[inline]
+    fn skip_until(&mut self, delim: &str) -> bool {
+        let delim = delim.as_bytes();
+
+        if delim.is_empty() {
+            return true;
+        }
+
+        match self.mmap[self.pos..].windows(delim.len()).position(|window| window == delim) {
+            Some(offset) => {
+                self.pos += offset;
+                true
+            },
+            None => false
+        }
+    }
+
+    #[inline]
+    fn between(&mut self, left: char, right: char) -> bool {
+        let len = left.len_utf8();
+
+        if len != right.len_utf8() {
+            panic!("ranges should have same-sized UTF-8 limits");
+        }
+
+        let to = self.pos + len;
+
+        if to <= self.mmap.len() {
+            if let Ok(string) = str::from_utf8(&self.mmap[self.pos..to]) {
+                let c = string.chars().next().unwrap();
+
+                let result = left <= c && c <= right;
+
+                if result {
+                    self.pos += len;
+                }
+
+                result
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    use super::super::super::Input;
+    use super::FileInput;
+
+    fn write_temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = env::temp_dir().join(name);
+        let mut file = fs::File::create(&path).unwrap();
+
+        file.write_all(contents).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn parts() {
+        let path = write_temp_file("pest-file-input-parts", b"asdasdf");
+        let mut input = FileInput::open(&path).unwrap();
+
+        assert!(input.matches("asd"));
+        assert!(input.matches("asdf"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn len() {
+        let path = write_temp_file("pest-file-input-len", b"asdasdf");
+        let input = FileInput::open(&path).unwrap();
+
+        assert_eq!(input.len(), 7);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn line_col() {
+        let path = write_temp_file("pest-file-input-line-col", b"a\rb\nc\r\nd");
+        let input = FileInput::open(&path).unwrap();
+
+        assert_eq!(input.line_col(0), (1, 1));
+        assert_eq!(input.line_col(6), (4, 1));
+        assert_eq!(input.line_col(7), (4, 1));
+        assert_eq!(input.line_col(8), (4, 2));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn validate() {
+        let path = write_temp_file("pest-file-input-validate", b"asd");
+        let input = FileInput::open(&path).unwrap();
+
+        assert!(input.validate().is_ok());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn between() {
+        let path = write_temp_file("pest-file-input-between", b"bbbb");
+        let mut input = FileInput::open(&path).unwrap();
+
+        assert!(input.between('a', 'c'));
+        assert!(input.between('b', 'b'));
+        assert!(!input.between('a', 'a'));
+        assert!(!input.between('c', 'c'));
+
+        assert_eq!(input.pos(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn matches_insensitive() {
+        let path = write_temp_file("pest-file-input-matches-insensitive", b"SeLeCt");
+        let mut input = FileInput::open(&path).unwrap();
+
+        assert!(input.matches_insensitive("select"));
+        assert_eq!(input.pos(), 6);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn skip_until() {
+        let path = write_temp_file("pest-file-input-skip-until", b"asd -- qwe");
+        let mut input = FileInput::open(&path).unwrap();
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 4);
+        assert!(!input.skip_until("nope"));
+        assert_eq!(input.pos(), 4);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn skip_until_ignores_invalid_utf8_elsewhere() {
+        let path = write_temp_file("pest-file-input-skip-until-non-utf8",
+                                    &[0xff, b'-', b'-', 0xfe]);
+        let mut input = FileInput::open(&path).unwrap();
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `trait` that defines an input for a parser.
+pub trait Input {
+    /// Returns length of the input.
+    fn len(&self) -> usize;
+
+    /// Returns current position of the input.
+    fn pos(&self) -> usize;
+
+    /// Set current position of the input.
+    fn set_pos(&mut self, pos: usize);
+
+    /// Slices input between `start` and `end`.
+    fn slice(&self, start: usize, end: usize) -> &str;
+
+    /// Slices input between `start` and `end`, returning raw bytes instead of a `&str`. Defaults
+    /// to borrowing the bytes behind `slice`, so like `slice` the default panics when `start` or
+    /// `end` does not lie on a char boundary.
+    #[inline]
+    fn slice_bytes(&self, start: usize, end: usize) -> &[u8] {
+        self.slice(start, end).as_bytes()
+    }
+
+    /// Gets line and column of `pos`.
+    fn line_col(&self, pos: usize) -> (usize, usize);
+
+    /// Matches `string` to input and advances `pos` on success.
+    fn matches(&mut self, string: &str) -> bool;
+
+    /// Matches `string` to input ignoring ASCII case and advances `pos` on success, exactly
+    /// like `matches`. Compares raw bytes via `slice_bytes` rather than slicing a `&str`, since
+    /// `to` is not guaranteed to land on a char boundary.
+    #[inline]
+    fn matches_insensitive(&mut self, string: &str) -> bool {
+        let pos = self.pos();
+        let to = pos + string.len();
+
+        if to <= self.len() {
+            let result = self.slice_bytes(pos, to).eq_ignore_ascii_case(string.as_bytes());
+
+            if result {
+                self.set_pos(to);
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    /// Matches if a `char` lies between `left` and `right`, advancing `pos` on success.
+    fn between(&mut self, left: char, right: char) -> bool;
+
+    /// Scans forward from `pos` for the next occurrence of `delim`, moving `pos` to the start
+    /// of the match and returning `true` if found; otherwise leaves `pos` unchanged and returns
+    /// `false`.
+    #[inline]
+    fn skip_until(&mut self, delim: &str) -> bool {
+        let pos = self.pos();
+
+        match self.slice(pos, self.len()).find(delim) {
+            Some(offset) => {
+                self.set_pos(pos + offset);
+                true
+            },
+            None => false
+        }
+    }
+}
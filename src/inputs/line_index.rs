@@ -0,0 +1,55 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Shared byte-offset indexing used by `Input` impls to answer `line_col` in O(log n).
+
+/// Builds the byte offset starting each line of `bytes`. A line broken by `"\r\n"` is recorded
+/// at the offset of the `'\n'`, collapsing the pair into a single break while still treating a
+/// lone `'\r'` or `'\n'` as a break on its own.
+pub fn starts(bytes: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' => {
+                if bytes.get(i + 1) == Some(&b'\n') {
+                    starts.push(i + 1);
+                    i += 2;
+                } else {
+                    starts.push(i + 1);
+                    i += 1;
+                }
+            },
+            b'\n' => {
+                starts.push(i + 1);
+                i += 1;
+            },
+            _ => i += 1
+        }
+    }
+
+    starts
+}
+
+/// Finds the 0-based line that `pos` falls on, given the offsets returned by `starts`.
+pub fn line_of(line_starts: &[usize], pos: usize) -> usize {
+    match line_starts.binary_search(&pos) {
+        Ok(line) => line,
+        Err(line) => line - 1
+    }
+}
+
+/// Returns the offset where a line's real content begins. A line that starts on the `'\n'` of a
+/// collapsed `"\r\n"` break has no content until one byte later.
+pub fn content_start(bytes: &[u8], start: usize) -> usize {
+    if start > 0 && bytes.get(start) == Some(&b'\n') && bytes.get(start - 1) == Some(&b'\r') {
+        start + 1
+    } else {
+        start
+    }
+}
@@ -0,0 +1,19 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod bytes_input;
+mod file_input;
+mod input;
+mod line_index;
+mod str_input;
+mod string_input;
+
+pub use self::bytes_input::BytesInput;
+pub use self::file_input::FileInput;
+pub use self::input::Input;
+pub use self::str_input::StrInput;
+pub use self::string_input::StringInput;
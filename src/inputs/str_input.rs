@@ -0,0 +1,276 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::{Ref, RefCell};
+
+use super::super::Input;
+use super::line_index;
+
+/// A `struct` useful for matching `&str`s without copying them, unlike `StringInput`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::StrInput;
+/// let mut input = StrInput::new("asdasdf");
+///
+/// assert!(input.matches("asd"));
+/// assert!(input.matches("asdf"));
+/// assert!(!input.matches("nope"));
+/// ```
+pub struct StrInput<'a> {
+    string: &'a str,
+    pos: usize,
+    line_starts: RefCell<Option<Vec<usize>>>
+}
+
+impl<'a> StrInput<'a> {
+    /// Creates a new `StrInput` from a `&'a str`, borrowing it rather than copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::StrInput;
+    /// let input = StrInput::new("asd");
+    ///
+    /// assert_eq!(input.len(), 3);
+    /// ```
+    pub fn new(string: &'a str) -> StrInput<'a> {
+        StrInput {
+            string,
+            pos: 0,
+            line_starts: RefCell::new(None)
+        }
+    }
+
+    /// Rebuilds a `StrInput` from a previous one's state, reusing an already computed
+    /// line-start index instead of discarding it. Used by `StringInput` to delegate to a
+    /// borrowed view of its own data without losing the `line_col` cache across calls.
+    pub(super) fn with_state(string: &'a str, pos: usize,
+                              line_starts: Option<Vec<usize>>) -> StrInput<'a> {
+        StrInput {
+            string,
+            pos,
+            line_starts: RefCell::new(line_starts)
+        }
+    }
+
+    /// Tears the `StrInput` back down into its position and line-start cache, handing
+    /// ownership of the cache back to a wrapping `Input`.
+    pub(super) fn into_state(self) -> (usize, Option<Vec<usize>>) {
+        (self.pos, self.line_starts.into_inner())
+    }
+
+    /// Returns the byte offset starting each line, building and caching the index on first use.
+    fn line_starts(&self) -> Ref<'_, Vec<usize>> {
+        if self.line_starts.borrow().is_none() {
+            *self.line_starts.borrow_mut() = Some(line_index::starts(self.string.as_bytes()));
+        }
+
+        Ref::map(self.line_starts.borrow(), |starts| starts.as_ref().unwrap())
+    }
+}
+
+impl<'a> Input for StrInput<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.string.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &str {
+        &self.string[start..end]
+    }
+
+    // Overrides the trait default, which goes through `slice` and so would panic on a `start`
+    // or `end` that doesn't land on a char boundary.
+    #[inline]
+    fn slice_bytes(&self, start: usize, end: usize) -> &[u8] {
+        &self.string.as_bytes()[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        if pos > self.string.len() {
+            panic!("position out of bounds");
+        }
+
+        let starts = self.line_starts();
+        let line = line_index::line_of(&starts, pos);
+        let content_start = line_index::content_start(self.string.as_bytes(), starts[line]);
+
+        let col = if pos <= content_start {
+            1
+        } else {
+            self.string[content_start..pos].chars().count() + 1
+        };
+
+        (line + 1, col)
+    }
+
+    #[inline]
+    fn matches(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.string.len() {
+            let result = &self.string[self.pos..to] == string;
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn between(&mut self, left: char, right: char) -> bool {
+        let len = left.len_utf8();
+
+        if len != right.len_utf8() {
+            panic!("ranges should have same-sized UTF-8 limits");
+        }
+
+        let to = self.pos + len;
+
+        if to <= self.string.len() {
+            if let Some(string) = self.string.get(self.pos..to) {
+                let c = string.chars().next().unwrap();
+
+                let result = left <= c && c <= right;
+
+                if result {
+                    self.pos += len;
+                }
+
+                result
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::StrInput;
+
+    #[test]
+    fn empty() {
+        let mut input = StrInput::new("");
+
+        assert!(input.matches(""));
+        assert!(!input.matches("a"));
+    }
+
+    #[test]
+    fn parts() {
+        let mut input = StrInput::new("asdasdf");
+
+        assert!(input.matches("asd"));
+        assert!(input.matches("asdf"));
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(StrInput::new("asdasdf").len(), 7);
+    }
+
+    #[test]
+    fn pos() {
+        let mut input = StrInput::new("asdasdf");
+
+        assert_eq!(input.pos(), 0);
+        assert!(input.matches("asd"));
+        assert_eq!(input.pos(), 3);
+        assert!(input.matches("asdf"));
+        assert_eq!(input.pos(), 7);
+
+        input.set_pos(3);
+
+        assert_eq!(input.pos(), 3);
+        assert!(input.matches("asdf"));
+        assert_eq!(input.pos(), 7);
+    }
+
+    #[test]
+    fn slice() {
+        let input = StrInput::new("asdasdf");
+
+        assert_eq!(input.slice(1, 3), "sd");
+    }
+
+    #[test]
+    fn line_col() {
+        let input = StrInput::new("a\rb\nc\r\nd");
+
+        assert_eq!(input.line_col(0), (1, 1));
+        assert_eq!(input.line_col(1), (1, 2));
+        assert_eq!(input.line_col(2), (2, 1));
+        assert_eq!(input.line_col(3), (2, 2));
+        assert_eq!(input.line_col(4), (3, 1));
+        assert_eq!(input.line_col(5), (3, 2));
+        assert_eq!(input.line_col(6), (4, 1));
+        assert_eq!(input.line_col(7), (4, 1));
+        assert_eq!(input.line_col(8), (4, 2));
+    }
+
+    #[test]
+    fn between() {
+        let mut input = StrInput::new("bbbb");
+
+        assert!(input.between('a', 'c'));
+        assert!(input.between('b', 'b'));
+        assert!(!input.between('a', 'a'));
+        assert!(!input.between('c', 'c'));
+
+        assert_eq!(input.pos(), 2);
+    }
+
+    #[test]
+    fn matches_insensitive() {
+        let mut input = StrInput::new("SeLeCt");
+
+        assert!(input.matches_insensitive("select"));
+        assert_eq!(input.pos(), 6);
+    }
+
+    #[test]
+    fn matches_insensitive_does_not_panic_on_non_char_boundary() {
+        let mut input = StrInput::new("é b");
+
+        assert!(!input.matches_insensitive("e"));
+        assert_eq!(input.pos(), 0);
+    }
+
+    #[test]
+    fn skip_until() {
+        let mut input = StrInput::new("asd -- qwe");
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 4);
+        assert!(!input.skip_until("nope"));
+        assert_eq!(input.pos(), 4);
+    }
+}
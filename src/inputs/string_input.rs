@@ -5,12 +5,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::iter::Peekable;
-use std::str::{self, Chars};
+use std::cell::RefCell;
 
 use super::super::Input;
+use super::str_input::StrInput;
 
-/// A `struct` useful for matching in-memory `String`s.
+/// A `struct` useful for matching in-memory `String`s. An owning wrapper around `StrInput`,
+/// for callers who'd rather hand over a `String` than manage its borrow themselves; prefer
+/// `StrInput` directly to avoid the `to_owned` copy.
 ///
 /// # Examples
 ///
@@ -25,7 +27,8 @@ use super::super::Input;
 /// ```
 pub struct StringInput {
     string: String,
-    pos: usize
+    pos: usize,
+    line_starts: RefCell<Option<Vec<usize>>>
 }
 
 impl StringInput {
@@ -43,9 +46,16 @@ impl StringInput {
     pub fn new(string: &str) -> StringInput {
         StringInput {
             string: string.to_owned(),
-            pos : 0
+            pos: 0,
+            line_starts: RefCell::new(None)
         }
     }
+
+    /// Borrows this `StringInput`'s data as a `StrInput`, handing over the `line_col` cache
+    /// rather than cloning it so repeated calls stay amortized O(log n).
+    fn as_str_input(&self) -> StrInput<'_> {
+        StrInput::with_state(&self.string, self.pos, self.line_starts.replace(None))
+    }
 }
 
 impl Input for StringInput {
@@ -69,86 +79,46 @@ impl Input for StringInput {
         &self.string[start..end]
     }
 
+    // Overrides the trait default, which goes through `slice` and so would panic on a `start`
+    // or `end` that doesn't land on a char boundary.
+    #[inline]
+    fn slice_bytes(&self, start: usize, end: usize) -> &[u8] {
+        &self.string.as_bytes()[start..end]
+    }
+
     #[inline]
     fn line_col(&self, pos: usize) -> (usize, usize) {
-        fn find(chars: &mut Peekable<Chars>, pos: usize,
-                current: (usize, usize)) -> (usize, usize) {
-            if pos == 0 {
-                current
-            } else {
-                match chars.next() {
-                    Some('\r') => {
-                        if let Some(&'\n') = chars.peek() {
-                            chars.next();
-
-                            if pos == 1 {
-                                find(chars, pos - 1, (current.0 + 1, 1))
-                            } else {
-                                find(chars, pos - 2, (current.0 + 1, 1))
-                            }
-                        } else {
-                            find(chars, pos - 1, (current.0 + 1, 1))
-                        }
-                    },
-                    Some('\n') => find(chars, pos - 1, (current.0 + 1, 1)),
-                    Some(_)    => find(chars, pos - 1, (current.0, current.1 + 1)),
-                    None       => unreachable!()
-                }
-            }
-        }
+        let input = self.as_str_input();
+        let result = input.line_col(pos);
+        let (_, line_starts) = input.into_state();
 
-        if pos > self.string.len() {
-            panic!("position out of bounds");
-        }
+        self.line_starts.replace(line_starts);
 
-        find(&mut self.string.chars().peekable(), pos, (1, 1))
+        result
     }
 
     #[inline]
     fn matches(&mut self, string: &str) -> bool {
-        let to = self.pos + string.len();
+        let mut input = self.as_str_input();
+        let result = input.matches(string);
+        let (pos, line_starts) = input.into_state();
 
-        if to <= self.string.len() {
-            let slice = unsafe { self.string.slice_unchecked(self.pos, to) };
-            let result = slice == string;
+        self.pos = pos;
+        self.line_starts.replace(line_starts);
 
-            if result {
-                self.pos = to;
-            }
-
-            result
-        } else {
-            false
-        }
+        result
     }
 
     #[inline]
     fn between(&mut self, left: char, right: char) -> bool {
-        let len = left.len_utf8();
-
-        if len != right.len_utf8() {
-            panic!("ranges should have same-sized UTF-8 limits");
-        }
-
-        let to = self.pos + len;
-
-        if to <= self.string.len() {
-            if let Ok(string) = str::from_utf8(&self.string.as_bytes()[self.pos..to]) {
-                let c = string.chars().next().unwrap();
-
-                let result = left <= c && c <= right;
+        let mut input = self.as_str_input();
+        let result = input.between(left, right);
+        let (pos, line_starts) = input.into_state();
 
-                if result {
-                    self.pos += len;
-                }
+        self.pos = pos;
+        self.line_starts.replace(line_starts);
 
-                result
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+        result
     }
 }
 
@@ -228,4 +198,30 @@ mod tests {
 
         assert_eq!(input.pos(), 2);
     }
+
+    #[test]
+    fn matches_insensitive() {
+        let mut input = StringInput::new("SeLeCt");
+
+        assert!(input.matches_insensitive("select"));
+        assert_eq!(input.pos(), 6);
+    }
+
+    #[test]
+    fn matches_insensitive_does_not_panic_on_non_char_boundary() {
+        let mut input = StringInput::new("é b");
+
+        assert!(!input.matches_insensitive("e"));
+        assert_eq!(input.pos(), 0);
+    }
+
+    #[test]
+    fn skip_until() {
+        let mut input = StringInput::new("asd -- qwe");
+
+        assert!(input.skip_until("--"));
+        assert_eq!(input.pos(), 4);
+        assert!(!input.skip_until("nope"));
+        assert_eq!(input.pos(), 4);
+    }
 }
@@ -0,0 +1,16 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! # pest
+//!
+//! Elegant, efficient grammars for parsing.
+
+extern crate memmap;
+
+mod inputs;
+
+pub use inputs::{BytesInput, FileInput, Input, StrInput, StringInput};